@@ -17,32 +17,258 @@
 use crate::shutdown;
 use crate::task;
 use crate::Result as ClientResult;
-use dragonfly_api::common::v2::{Piece, Task};
+use dragonfly_api::common::v2::{Piece, Range, Task};
 use dragonfly_api::dfdaemon::v2::{
     dfdaemon_client::DfdaemonClient as DfdaemonGRPCClient,
     dfdaemon_server::{Dfdaemon, DfdaemonServer as DfdaemonGRPCServer},
-    sync_pieces_request, sync_pieces_response, DeleteTaskRequest, DownloadTaskRequest,
-    DownloadTaskResponse, GetPieceNumbersRequest, GetPieceNumbersResponse, InterestedPiecesRequest,
-    InterestedPiecesResponse, StatTaskRequest as DfdaemonStatTaskRequest, SyncPiecesRequest,
-    SyncPiecesResponse, UploadTaskRequest,
+    sync_pieces_request, sync_pieces_response, upload_task_request, DeleteTaskRequest,
+    DownloadTaskRequest, DownloadTaskResponse, GetPieceNumbersRequest, GetPieceNumbersResponse,
+    InterestedPiecesRequest, InterestedPiecesResponse, StatTaskRequest as DfdaemonStatTaskRequest,
+    SyncPiecesRequest, SyncPiecesResponse, UploadTaskMetadataRequest, UploadTaskRequest,
 };
 use dragonfly_api::scheduler::v2::StatTaskRequest as SchedulerStatTaskRequest;
+use futures::SinkExt;
+use rand::Rng;
+use std::error::Error as _;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::io::AsyncReadExt;
+use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio::net::{UnixListener, UnixStream};
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::{ReceiverStream, UnixListenerStream};
+use tokio_stream::StreamExt;
+use tokio_util::sync::PollSender;
 use tonic::codec::CompressionEncoding;
 use tonic::{
     transport::{Channel, Endpoint, Server, Uri},
-    Request, Response, Status,
+    Code, Request, Response, Status, Streaming,
 };
 use tower::service_fn;
 use tracing::{error, info};
 
+// SYNC_PIECES_FRAME_SIZE is the size of each piece content frame streamed by sync_pieces.
+const SYNC_PIECES_FRAME_SIZE: usize = 64 * 1024;
+
+// verify_piece_digest recomputes the digest of a piece reader's content and compares it
+// against the expected digest, reading in fixed-size frames so the check stays memory-bounded
+// regardless of piece size. The hash algorithm is parsed from expected_digest's own
+// "<algorithm>:<hex>" prefix rather than assumed, so storage using a digest algorithm other than
+// the one this crate happens to hash with isn't mistaken for corruption. An unrecognized format
+// or algorithm is treated as unverifiable, not as a mismatch: refusing to serve otherwise-valid
+// content because this crate can't check it would be worse than skipping the check.
+async fn verify_piece_digest(
+    reader: &mut (impl tokio::io::AsyncRead + Unpin),
+    expected_digest: &str,
+) -> std::io::Result<bool> {
+    let Some(algorithm) = task::digest_algorithm(expected_digest) else {
+        return Ok(true);
+    };
+
+    match task::compute_piece_digest(reader, algorithm, SYNC_PIECES_FRAME_SIZE).await? {
+        Some(actual_digest) => Ok(actual_digest == expected_digest),
+        None => Ok(true),
+    }
+}
+
+// persist_uploaded_piece verifies a single uploaded piece against the digest the uploader
+// declared in the upload metadata and, if it matches, writes it into local storage through
+// `task.piece`. On mismatch the upload is refused rather than persisting corrupt content.
+async fn persist_uploaded_piece(
+    task: &task::Task,
+    task_id: &str,
+    piece_number: i32,
+    metadata: &UploadTaskMetadataRequest,
+    content: Vec<u8>,
+) -> Result<(), Status> {
+    let expected_digest = metadata
+        .piece_digests
+        .get(piece_number as usize)
+        .ok_or_else(|| {
+            error!("uploaded piece {} has no expected digest", piece_number);
+            Status::invalid_argument(format!(
+                "missing expected digest for piece {}",
+                piece_number
+            ))
+        })?;
+
+    let algorithm = task::digest_algorithm(expected_digest).ok_or_else(|| {
+        error!(
+            "uploaded piece {} has an unrecognized expected digest format: {}",
+            piece_number, expected_digest
+        );
+        Status::invalid_argument(format!(
+            "unrecognized digest format for piece {}",
+            piece_number
+        ))
+    })?;
+
+    // Unlike verifying already-stored content, an upload is new content arriving over the
+    // wire: an algorithm this crate can't compute is refused rather than trusted, since there
+    // is no prior "it was fine in storage" to fall back on.
+    let actual_digest = task::compute_digest(&content, algorithm).ok_or_else(|| {
+        error!(
+            "uploaded piece {} declares unsupported digest algorithm {}",
+            piece_number, algorithm
+        );
+        Status::invalid_argument(format!("unsupported digest algorithm {}", algorithm))
+    })?;
+    if actual_digest != *expected_digest {
+        error!(
+            "uploaded piece {} digest mismatch (expected {}, got {})",
+            piece_number, expected_digest, actual_digest
+        );
+        return Err(Status::data_loss(format!(
+            "piece {} digest mismatch",
+            piece_number
+        )));
+    }
+
+    task.piece
+        .write_uploaded(task_id, piece_number, &content, &actual_digest)
+        .await
+        .map_err(|e| {
+            error!(
+                "write uploaded piece {} to local storage: {}",
+                piece_number, e
+            );
+            Status::internal(e.to_string())
+        })
+}
+
+// RANGE_READER_CHUNK_SIZE is the size of each chunk read out of a piece when serving a byte
+// range request.
+const RANGE_READER_CHUNK_SIZE: usize = 64 * 1024;
+
+// PieceRangeReader is a forward-seekable view over a task's already-assembled pieces for a
+// single byte range request. Given a start offset it locates the first piece that contains it
+// (computing the piece number and the intra-piece offset), skips every piece before it without
+// reading a single byte, and only reads-and-discards bytes within that first piece to reach the
+// exact offset. This keeps large skips cheap even though the per-piece readers handed out by
+// local storage cannot truly seek.
+struct PieceRangeReader {
+    task: Arc<task::Task>,
+    task_id: String,
+    pieces: std::collections::VecDeque<(i32, u64)>,
+    skip_in_first: u64,
+    remaining: u64,
+    current: Option<(i32, u64, Box<dyn AsyncRead + Send + Unpin>)>,
+}
+
+impl PieceRangeReader {
+    // new builds a reader that yields `length` bytes starting at `start` within the task's
+    // assembled content, or fewer if the locally stored pieces end before `start + length`.
+    async fn new(
+        task: Arc<task::Task>,
+        task_id: String,
+        start: u64,
+        length: u64,
+    ) -> Result<Self, Status> {
+        let mut pieces = task.piece.get_all(&task_id).map_err(|e| {
+            error!("get piece metadata from local storage: {}", e);
+            Status::internal(e.to_string())
+        })?;
+        pieces.sort_by_key(|piece| piece.number);
+
+        let mut queue = std::collections::VecDeque::new();
+        let mut skip_in_first = 0u64;
+        let mut found_first = false;
+        for piece in pieces {
+            let piece_end = piece.offset + piece.length;
+            if !found_first {
+                if start >= piece_end {
+                    // The whole piece is before the requested range: skip it cheaply.
+                    continue;
+                }
+
+                skip_in_first = start - piece.offset;
+                found_first = true;
+            }
+
+            queue.push_back((piece.number, piece.offset));
+        }
+
+        Ok(Self {
+            task,
+            task_id,
+            pieces: queue,
+            skip_in_first,
+            remaining: length,
+            current: None,
+        })
+    }
+
+    // next_chunk returns the piece number, the absolute offset within the task and the bytes
+    // of the next chunk of the requested range, or None once every requested byte has been
+    // yielded or the locally stored pieces have run out.
+    async fn next_chunk(&mut self) -> Result<Option<(i32, u64, Vec<u8>)>, Status> {
+        while self.remaining > 0 {
+            if self.current.is_none() {
+                let (piece_number, piece_offset) = match self.pieces.pop_front() {
+                    Some(entry) => entry,
+                    None => return Ok(None),
+                };
+
+                let mut reader = self
+                    .task
+                    .piece
+                    .download_from_local_peer(&self.task_id, piece_number)
+                    .await
+                    .map_err(|e| {
+                        error!("get piece content from local peer: {}", e);
+                        Status::internal(e.to_string())
+                    })?;
+
+                // Naive forward seek: within the first piece, read-and-discard the bytes
+                // before the requested start offset. Every later piece starts exactly on a
+                // piece boundary, so this only ever runs once.
+                let skip = std::mem::take(&mut self.skip_in_first);
+                let mut to_skip = skip;
+                let mut discard = vec![0u8; RANGE_READER_CHUNK_SIZE];
+                while to_skip > 0 {
+                    let n = reader
+                        .read(&mut discard[..RANGE_READER_CHUNK_SIZE.min(to_skip as usize)])
+                        .await
+                        .map_err(|e| {
+                            error!("discard piece content while seeking: {}", e);
+                            Status::internal(e.to_string())
+                        })?;
+                    if n == 0 {
+                        break;
+                    }
+
+                    to_skip -= n as u64;
+                }
+
+                self.current = Some((piece_number, piece_offset + skip, Box::new(reader)));
+            }
+
+            let (piece_number, offset, reader) = self.current.as_mut().unwrap();
+            let want = RANGE_READER_CHUNK_SIZE.min(self.remaining as usize);
+            let mut buf = vec![0u8; want];
+            let n = reader.read(&mut buf).await.map_err(|e| {
+                error!("read piece content: {}", e);
+                Status::internal(e.to_string())
+            })?;
+            if n == 0 {
+                // This piece is exhausted; move on to the next one.
+                self.current = None;
+                continue;
+            }
+
+            buf.truncate(n);
+            let chunk_offset = *offset;
+            let chunk_piece_number = *piece_number;
+            *offset += n as u64;
+            self.remaining -= n as u64;
+            return Ok(Some((chunk_piece_number, chunk_offset, buf)));
+        }
+
+        Ok(None)
+    }
+}
+
 // DfdaemonServer is the grpc server of the dfdaemon.
 pub struct DfdaemonServer {
     // addr is the address of the grpc server.
@@ -82,6 +308,16 @@ impl DfdaemonServer {
 
     // run starts the metrics server.
     pub async fn run(&self) {
+        // Spawn the background scrubber so locally stored pieces are periodically
+        // re-verified against their digests and corrupt ones are marked for re-download,
+        // independently of whatever gets served over the grpc service below.
+        let scrub_task = self.task.clone();
+        tokio::spawn(async move {
+            task::scrub::Scrubber::new(scrub_task, task::DEFAULT_SCRUB_SCAN_INTERVAL)
+                .run()
+                .await;
+        });
+
         // Register the reflection service.
         let reflection = tonic_reflection::server::Builder::configure()
             .register_encoded_file_descriptor_set(dragonfly_api::FILE_DESCRIPTOR_SET)
@@ -180,98 +416,143 @@ impl Dfdaemon for DfdaemonServerHandler {
     // sync_pieces syncs the pieces.
     async fn sync_pieces(
         &self,
-        request: Request<SyncPiecesRequest>,
+        request: Request<Streaming<SyncPiecesRequest>>,
     ) -> Result<Response<Self::SyncPiecesStream>, Status> {
-        // Clone the request.
-        let request = request.into_inner();
-
         // Clone the task.
         let task = self.task.clone();
 
-        // Get the task id from the request.
-        let task_id = request.task_id.clone();
-
-        // Get the interested piece numbers from the request.
-        let interested_piece_numbers = match request.request {
-            Some(sync_pieces_request::Request::InterestedPiecesRequest(
-                InterestedPiecesRequest { piece_numbers },
-            )) => piece_numbers,
-            _ => {
-                error!("missing interested pieces request");
-                return Err(Status::invalid_argument(
-                    "missing interested pieces request",
-                ));
-            }
-        };
+        // Get the in stream of the interested pieces requests.
+        let mut in_stream = request.into_inner();
 
         // Initialize stream channel.
         let (out_stream_tx, out_stream_rx) = mpsc::channel(128);
         tokio::spawn(async move {
-            for interested_piece_number in interested_piece_numbers {
-                // Get the piece metadata from the local storage.
-                let piece = match task.piece.get(&task_id, interested_piece_number) {
-                    Ok(piece) => piece,
+            while let Some(result) = in_stream.next().await {
+                // Get the request from the in stream.
+                let request = match result {
+                    Ok(request) => request,
                     Err(e) => {
-                        error!("get piece metadata from local storage: {}", e);
-                        continue;
+                        error!("receive interested pieces request: {}", e);
+                        break;
                     }
                 };
 
-                // Check whether the piece exists.
-                let piece = match piece {
-                    Some(piece) => piece,
-                    None => {
-                        error!("piece {} not found", interested_piece_number);
+                // Get the task id and the interested piece numbers from the request.
+                let (task_id, interested_piece_numbers) = match request.request {
+                    Some(sync_pieces_request::Request::InterestedPiecesRequest(
+                        InterestedPiecesRequest {
+                            task_id,
+                            piece_numbers,
+                        },
+                    )) => (task_id, piece_numbers),
+                    _ => {
+                        error!("missing interested pieces request");
                         continue;
                     }
                 };
 
-                // Get the piece content from the local storage.
-                let mut reader = match task
-                    .piece
-                    .download_from_local_peer(&task_id, interested_piece_number)
-                    .await
-                {
-                    Ok(reader) => reader,
-                    Err(e) => {
-                        error!("get piece content from local peer: {}", e);
-                        continue;
-                    }
-                };
+                for interested_piece_number in interested_piece_numbers {
+                    // Get the piece metadata from the local storage.
+                    let piece = match task.piece.get(&task_id, interested_piece_number) {
+                        Ok(piece) => piece,
+                        Err(e) => {
+                            error!("get piece metadata from local storage: {}", e);
+                            continue;
+                        }
+                    };
 
-                // Read the content of the piece.
-                let mut content = Vec::new();
-                match reader.read_to_end(&mut content).await {
-                    Ok(_) => {}
-                    Err(e) => {
-                        error!("read piece content: {}", e);
-                        continue;
+                    // Check whether the piece exists.
+                    let piece = match piece {
+                        Some(piece) => piece,
+                        None => {
+                            error!("piece {} not found", interested_piece_number);
+                            continue;
+                        }
+                    };
+
+                    // Get the piece content from the local storage.
+                    let mut reader = match task
+                        .piece
+                        .download_from_local_peer(&task_id, interested_piece_number)
+                        .await
+                    {
+                        Ok(reader) => reader,
+                        Err(e) => {
+                            error!("get piece content from local peer: {}", e);
+                            continue;
+                        }
+                    };
+
+                    // Stream the piece content in fixed-size frames through a PollSender,
+                    // hashing each frame as it is read so verification costs one disk read
+                    // instead of two. Per-stream memory is bounded regardless of piece size, by
+                    // the frames queued in the out stream channel (up to its capacity of 128)
+                    // plus the frame being read; it is not limited to a single frame. Awaiting
+                    // the sink's readiness before reading the next frame propagates the
+                    // channel's backpressure back to the disk reader once that queue is full,
+                    // instead of buffering the whole piece in this task.
+                    let mut digest_verifier = task::PieceDigestStreamVerifier::new(&piece.digest);
+                    let mut sink = PollSender::new(out_stream_tx.clone());
+                    let mut read_offset = 0u64;
+                    let mut frame = vec![0u8; SYNC_PIECES_FRAME_SIZE];
+                    loop {
+                        let n = match reader.read(&mut frame).await {
+                            Ok(0) => break,
+                            Ok(n) => n,
+                            Err(e) => {
+                                error!("read piece content: {}", e);
+                                break;
+                            }
+                        };
+
+                        digest_verifier.update(&frame[..n]);
+
+                        if let Err(e) = sink
+                            .send(Ok(SyncPiecesResponse {
+                                response: Some(
+                                    sync_pieces_response::Response::InterestedPiecesResponse(
+                                        InterestedPiecesResponse {
+                                            piece: Some(Piece {
+                                                number: piece.number,
+                                                parent_id: None,
+                                                offset: piece.offset + read_offset,
+                                                length: n as u64,
+                                                digest: piece.digest.clone(),
+                                                content: Some(frame[..n].to_vec()),
+                                                traffic_type: None,
+                                                cost: None,
+                                                created_at: None,
+                                            }),
+                                        },
+                                    ),
+                                ),
+                            }))
+                            .await
+                        {
+                            error!("send to out stream: {}", e);
+                            break;
+                        }
+
+                        read_offset += n as u64;
                     }
-                };
 
-                // Send the interested pieces response.
-                out_stream_tx
-                    .send(Ok(SyncPiecesResponse {
-                        response: Some(sync_pieces_response::Response::InterestedPiecesResponse(
-                            InterestedPiecesResponse {
-                                piece: Some(Piece {
-                                    number: piece.number,
-                                    parent_id: None,
-                                    offset: piece.offset,
-                                    length: piece.length,
-                                    digest: piece.digest,
-                                    content: Some(content),
-                                    traffic_type: None,
-                                    cost: None,
-                                    created_at: None,
-                                }),
-                            },
-                        )),
-                    }))
-                    .await
-                    .unwrap_or_else(|e| {
-                        error!("send to out stream: {}", e);
-                    });
+                    // The piece has already been forwarded at this point, so a mismatch can no
+                    // longer be caught before serving it, and no error is sent back on this
+                    // long-lived stream for one corrupt piece. Mark the piece for re-download
+                    // instead.
+                    if !digest_verifier.finish(&piece.digest) {
+                        error!(
+                            "piece {} digest mismatch after streaming, marking for re-download",
+                            interested_piece_number
+                        );
+                        if let Err(e) = task
+                            .piece
+                            .mark_for_redownload(&task_id, interested_piece_number)
+                        {
+                            error!("mark piece for re-download: {}", e);
+                        }
+                    }
+                }
             }
         });
 
@@ -301,9 +582,63 @@ impl Dfdaemon for DfdaemonServerHandler {
         // Initialize stream channel.
         let (out_stream_tx, out_stream_rx) = mpsc::channel(128);
         tokio::spawn(async move {
-            match task.download_into_file(download).await {
+            let range = download.range.clone();
+
+            // When the caller only wants a sub-range of the task (e.g. an HTTP Range request
+            // proxied through the daemon), fetch only the pieces that overlap the range
+            // instead of materializing the whole task first, so skipping ahead in a large
+            // task stays cheap even before anything has been downloaded.
+            let download_result = match &range {
+                Some(range) => {
+                    task.download_into_storage_range(download.clone(), range.clone())
+                        .await
+                }
+                None => task.download_into_file(download.clone()).await,
+            };
+
+            match download_result {
                 Ok(mut download_progress_rx) => {
                     while let Some(finished_piece) = download_progress_rx.recv().await {
+                        // Verify the finished piece against its stored digest before telling
+                        // the caller it is ready, so corruption in local storage is never
+                        // propagated to other peers.
+                        let mut verify_reader = match task
+                            .piece
+                            .download_from_local_peer(&download.task_id, finished_piece.number)
+                            .await
+                        {
+                            Ok(reader) => reader,
+                            Err(e) => {
+                                error!("get piece content from local peer: {}", e);
+                                continue;
+                            }
+                        };
+
+                        match verify_piece_digest(&mut verify_reader, &finished_piece.digest).await
+                        {
+                            Ok(true) => {}
+                            Ok(false) => {
+                                error!(
+                                    "piece {} digest mismatch, refusing to serve corrupted content",
+                                    finished_piece.number
+                                );
+                                out_stream_tx
+                                    .send(Err(Status::data_loss(format!(
+                                        "piece {} digest mismatch",
+                                        finished_piece.number
+                                    ))))
+                                    .await
+                                    .unwrap_or_else(|e| {
+                                        error!("send to out stream: {}", e);
+                                    });
+                                continue;
+                            }
+                            Err(e) => {
+                                error!("verify piece digest: {}", e);
+                                continue;
+                            }
+                        }
+
                         out_stream_tx
                             .send(Ok(DownloadTaskResponse {
                                 piece: Some(Piece {
@@ -327,6 +662,62 @@ impl Dfdaemon for DfdaemonServerHandler {
                                 error!("send to out stream: {}", e);
                             });
                     }
+
+                    // The pieces overlapping the requested byte range (and only those) have
+                    // now been fetched into local storage by download_into_storage_range
+                    // above, so this only ever reads pieces that are already local.
+                    if let Some(range) = range {
+                        let mut range_reader = match PieceRangeReader::new(
+                            task.clone(),
+                            download.task_id.clone(),
+                            range.start,
+                            range.length,
+                        )
+                        .await
+                        {
+                            Ok(range_reader) => range_reader,
+                            Err(status) => {
+                                error!("build piece range reader: {}", status);
+                                out_stream_tx.send(Err(status)).await.unwrap_or_else(|e| {
+                                    error!("send to out stream: {}", e);
+                                });
+                                return;
+                            }
+                        };
+
+                        loop {
+                            match range_reader.next_chunk().await {
+                                Ok(Some((piece_number, offset, content))) => {
+                                    out_stream_tx
+                                        .send(Ok(DownloadTaskResponse {
+                                            piece: Some(Piece {
+                                                number: piece_number,
+                                                parent_id: None,
+                                                offset,
+                                                length: content.len() as u64,
+                                                digest: String::new(),
+                                                content: Some(content),
+                                                traffic_type: None,
+                                                cost: None,
+                                                created_at: None,
+                                            }),
+                                        }))
+                                        .await
+                                        .unwrap_or_else(|e| {
+                                            error!("send to out stream: {}", e);
+                                        });
+                                }
+                                Ok(None) => break,
+                                Err(status) => {
+                                    error!("read piece range: {}", status);
+                                    out_stream_tx.send(Err(status)).await.unwrap_or_else(|e| {
+                                        error!("send to out stream: {}", e);
+                                    });
+                                    break;
+                                }
+                            }
+                        }
+                    }
                 }
                 Err(e) => {
                     error!("download task: {}", e);
@@ -343,13 +734,127 @@ impl Dfdaemon for DfdaemonServerHandler {
         Ok(Response::new(ReceiverStream::new(out_stream_rx)))
     }
 
-    // upload_task tells the dfdaemon to upload the task.
+    // upload_task tells the dfdaemon to seed a task pushed by an external client. The first
+    // frame on the stream must carry the upload metadata (task id, total length, per-piece
+    // size and expected digests); every frame after that is an ordered content chunk. Chunks
+    // are assembled into pieces, each piece's digest is verified against what the uploader
+    // declared, and the piece is persisted through `task.piece` as soon as its boundary is
+    // crossed. Once every piece is persisted the task is registered with the scheduler so
+    // other peers can fetch it.
     async fn upload_task(
         &self,
-        request: Request<UploadTaskRequest>,
+        request: Request<Streaming<UploadTaskRequest>>,
     ) -> Result<Response<()>, Status> {
-        println!("upload_task: {:?}", request);
-        Err(Status::unimplemented("not implemented"))
+        // Clone the task.
+        let task = self.task.clone();
+
+        // Get the in stream of the upload task requests.
+        let mut in_stream = request.into_inner();
+
+        // The first frame must be the upload metadata.
+        let metadata = match in_stream.next().await {
+            Some(Ok(UploadTaskRequest {
+                request: Some(upload_task_request::Request::Metadata(metadata)),
+            })) => metadata,
+            Some(Ok(_)) => {
+                error!("first upload task frame is not metadata");
+                return Err(Status::invalid_argument(
+                    "first upload task frame must be metadata",
+                ));
+            }
+            Some(Err(e)) => {
+                error!("receive upload task metadata: {}", e);
+                return Err(Status::internal(e.to_string()));
+            }
+            None => {
+                error!("upload task stream closed before metadata");
+                return Err(Status::invalid_argument("missing upload task metadata"));
+            }
+        };
+
+        let task_id = metadata.task_id.clone();
+        let piece_length = metadata.piece_length as usize;
+        if piece_length == 0 {
+            error!("upload task metadata has zero piece length");
+            return Err(Status::invalid_argument("piece_length must be non-zero"));
+        }
+
+        // Buffer content until a full piece boundary is crossed, verify the piece's digest and
+        // persist it, then move on to the next piece number.
+        let mut piece_number = 0i32;
+        let mut piece_buffer = Vec::with_capacity(piece_length);
+        let mut received_length = 0u64;
+        while let Some(result) = in_stream.next().await {
+            let request = match result {
+                Ok(request) => request,
+                Err(e) => {
+                    error!("receive upload task chunk: {}", e);
+                    return Err(Status::internal(e.to_string()));
+                }
+            };
+
+            let chunk = match request.request {
+                Some(upload_task_request::Request::Chunk(chunk)) => chunk,
+                _ => {
+                    error!("missing upload task chunk");
+                    return Err(Status::invalid_argument("missing upload task chunk"));
+                }
+            };
+
+            received_length += chunk.content.len() as u64;
+            piece_buffer.extend_from_slice(&chunk.content);
+            while piece_buffer.len() >= piece_length {
+                let piece_content: Vec<u8> = piece_buffer.drain(..piece_length).collect();
+                persist_uploaded_piece(&task, &task_id, piece_number, &metadata, piece_content)
+                    .await?;
+                piece_number += 1;
+            }
+        }
+
+        // Persist whatever is left over as the final, possibly short, piece.
+        if !piece_buffer.is_empty() {
+            persist_uploaded_piece(&task, &task_id, piece_number, &metadata, piece_buffer).await?;
+            piece_number += 1;
+        }
+
+        // A client that disconnected mid-stream, or that lied about total_length or
+        // piece_digests, must not get registered and seeded to the P2P network missing its
+        // tail pieces. Refuse to register unless every declared byte and piece actually
+        // arrived.
+        if received_length != metadata.total_length {
+            error!(
+                "upload task {} received {} bytes, expected {}",
+                task_id, received_length, metadata.total_length
+            );
+            return Err(Status::invalid_argument(format!(
+                "received {} bytes, expected {}",
+                received_length, metadata.total_length
+            )));
+        }
+
+        if piece_number as usize != metadata.piece_digests.len() {
+            error!(
+                "upload task {} received {} pieces, expected {}",
+                task_id,
+                piece_number,
+                metadata.piece_digests.len()
+            );
+            return Err(Status::invalid_argument(format!(
+                "received {} pieces, expected {}",
+                piece_number,
+                metadata.piece_digests.len()
+            )));
+        }
+
+        // Register the fully persisted task with the scheduler so other peers can fetch it.
+        task.register_uploaded_task(&task_id, metadata.total_length)
+            .await
+            .map_err(|e| {
+                error!("register uploaded task with scheduler: {}", e);
+                Status::internal(e.to_string())
+            })?;
+
+        Ok(Response::new(()))
     }
 
     // stat_task gets the status of the task.
@@ -380,41 +885,194 @@ impl Dfdaemon for DfdaemonServerHandler {
     }
 }
 
-// DfdaemonClient is a wrapper of DfdaemonGRPCClient.
+// DfdaemonClientConfig configures the reconnection policy of DfdaemonClient.
+#[derive(Clone, Copy, Debug)]
+pub struct DfdaemonClientConfig {
+    // max_retries is the maximum number of re-dial attempts before a reconnect gives up.
+    pub max_retries: u32,
+
+    // initial_backoff is the backoff after the first failed re-dial attempt; the first attempt
+    // itself is made immediately. The backoff doubles after each further failure, and every
+    // wait is the backoff plus a random jitter of up to the backoff again.
+    pub initial_backoff: Duration,
+
+    // max_backoff is the ceiling that the exponential backoff is capped at, before jitter.
+    pub max_backoff: Duration,
+}
+
+// DfdaemonClientConfig default reconnection policy.
+impl Default for DfdaemonClientConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+// DfdaemonClientEndpoint remembers how the dfdaemon was dialed, so a broken channel can be
+// re-dialed transparently without leaking the address string on every reconnect.
+#[derive(Clone)]
+enum DfdaemonClientEndpoint {
+    // Tcp is a TCP endpoint reachable at the given address.
+    Tcp(String),
+
+    // Unix is a unix domain socket endpoint at the given path.
+    Unix(PathBuf),
+}
+
+// DfdaemonClientEndpoint implements dialing of the remembered endpoint.
+impl DfdaemonClientEndpoint {
+    // connect dials the endpoint and returns a fresh channel.
+    async fn connect(&self) -> ClientResult<Channel> {
+        let channel = match self {
+            DfdaemonClientEndpoint::Tcp(addr) => {
+                Channel::from_shared(addr.clone())?.connect().await?
+            }
+            DfdaemonClientEndpoint::Unix(socket_path) => {
+                // Ignore the uri because it is not used.
+                let socket_path = socket_path.clone();
+                Endpoint::try_from("http://[::]:50051")
+                    .unwrap()
+                    .connect_with_connector(service_fn(move |_: Uri| {
+                        UnixStream::connect(socket_path.clone())
+                    }))
+                    .await?
+            }
+        };
+
+        Ok(channel)
+    }
+}
+
+// is_reconnectable returns whether a failed rpc indicates the underlying connection is broken
+// and worth re-dialing, rather than an application-level error a reconnect would not fix.
+// `Cancelled` is routinely caller- or stream-initiated and `DeadlineExceeded` is a per-request
+// timeout; neither means the channel itself is dead, so re-dialing on them would tear down a
+// perfectly good connection under load. `Unavailable` and an underlying transport error are the
+// only two that actually indicate a broken channel.
+fn is_reconnectable(status: &Status) -> bool {
+    if status.code() == Code::Unavailable {
+        return true;
+    }
+
+    status
+        .source()
+        .map(|source| source.is::<tonic::transport::Error>())
+        .unwrap_or(false)
+}
+
+// jitter returns a random duration up to ceiling, used to spread out reconnect attempts from
+// multiple clients that broke at the same time. Clients that broke within the same instant
+// would otherwise pick near-identical delays and reconnect in lockstep, so this draws from an
+// RNG rather than the wall clock.
+fn jitter(ceiling: Duration) -> Duration {
+    Duration::from_millis(rand::thread_rng().gen_range(0..=ceiling.as_millis().max(1) as u64))
+}
+
+// build_dfdaemon_client wraps a channel with the grpc client options shared by every connect.
+fn build_dfdaemon_client(channel: Channel) -> DfdaemonGRPCClient<Channel> {
+    DfdaemonGRPCClient::new(channel)
+        .send_compressed(CompressionEncoding::Gzip)
+        .accept_compressed(CompressionEncoding::Gzip)
+        .max_decoding_message_size(usize::MAX)
+}
+
+// DfdaemonClient is a wrapper of DfdaemonGRPCClient that transparently re-dials the dfdaemon
+// when its channel breaks, instead of leaving every streaming call returning the same
+// transport error forever.
 #[derive(Clone)]
 pub struct DfdaemonClient {
-    // client is the grpc client of the dfdaemon.
-    pub client: DfdaemonGRPCClient<Channel>,
+    // client is the grpc client of the dfdaemon, held behind a lock so a broken connection can
+    // be swapped out for a freshly re-dialed one without requiring callers to hold `&mut self`.
+    client: Arc<std::sync::RwLock<DfdaemonGRPCClient<Channel>>>,
+
+    // endpoint remembers how to re-dial the dfdaemon.
+    endpoint: DfdaemonClientEndpoint,
+
+    // config is the reconnection policy.
+    config: DfdaemonClientConfig,
 }
 
 // DfdaemonClient implements the grpc client of the dfdaemon.
 impl DfdaemonClient {
-    // new creates a new DfdaemonClient.
+    // new creates a new DfdaemonClient with the default reconnection policy.
     pub async fn new(addr: String) -> ClientResult<Self> {
-        let channel = Channel::from_static(Box::leak(addr.into_boxed_str()))
-            .connect()
-            .await?;
-        let client = DfdaemonGRPCClient::new(channel)
-            .send_compressed(CompressionEncoding::Gzip)
-            .accept_compressed(CompressionEncoding::Gzip)
-            .max_decoding_message_size(usize::MAX);
-        Ok(Self { client })
+        Self::new_with_config(addr, DfdaemonClientConfig::default()).await
+    }
+
+    // new_with_config creates a new DfdaemonClient with a custom reconnection policy.
+    pub async fn new_with_config(addr: String, config: DfdaemonClientConfig) -> ClientResult<Self> {
+        let endpoint = DfdaemonClientEndpoint::Tcp(addr);
+        let channel = endpoint.connect().await?;
+        Ok(Self {
+            client: Arc::new(std::sync::RwLock::new(build_dfdaemon_client(channel))),
+            endpoint,
+            config,
+        })
     }
 
-    // new_unix creates a new DfdaemonClient with unix domain socket.
+    // new_unix creates a new DfdaemonClient with unix domain socket and the default
+    // reconnection policy.
     pub async fn new_unix(socket_path: PathBuf) -> ClientResult<Self> {
-        // Ignore the uri because it is not used.
-        let channel = Endpoint::try_from("http://[::]:50051")
-            .unwrap()
-            .connect_with_connector(service_fn(move |_: Uri| {
-                UnixStream::connect(socket_path.clone())
-            }))
-            .await?;
-        let client = DfdaemonGRPCClient::new(channel)
-            .send_compressed(CompressionEncoding::Gzip)
-            .accept_compressed(CompressionEncoding::Gzip)
-            .max_decoding_message_size(usize::MAX);
-        Ok(Self { client })
+        Self::new_unix_with_config(socket_path, DfdaemonClientConfig::default()).await
+    }
+
+    // new_unix_with_config creates a new DfdaemonClient with unix domain socket and a custom
+    // reconnection policy.
+    pub async fn new_unix_with_config(
+        socket_path: PathBuf,
+        config: DfdaemonClientConfig,
+    ) -> ClientResult<Self> {
+        let endpoint = DfdaemonClientEndpoint::Unix(socket_path);
+        let channel = endpoint.connect().await?;
+        Ok(Self {
+            client: Arc::new(std::sync::RwLock::new(build_dfdaemon_client(channel))),
+            endpoint,
+            config,
+        })
+    }
+
+    // client returns a cheap clone of the grpc client currently in use.
+    fn client(&self) -> DfdaemonGRPCClient<Channel> {
+        self.client.read().unwrap().clone()
+    }
+
+    // reconnect re-dials the dfdaemon with capped exponential backoff and jitter, swaps in the
+    // freshly connected client and returns a clone of it.
+    async fn reconnect(&self) -> ClientResult<DfdaemonGRPCClient<Channel>> {
+        let mut backoff = self.config.initial_backoff;
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match self.endpoint.connect().await {
+                Ok(channel) => {
+                    let client = build_dfdaemon_client(channel);
+                    *self.client.write().unwrap() = client.clone();
+                    info!("reconnected dfdaemon client after {} attempt(s)", attempt);
+                    return Ok(client);
+                }
+                Err(e) if attempt < self.config.max_retries => {
+                    error!("reconnect dfdaemon client (attempt {}): {}", attempt, e);
+                    tokio::time::sleep(backoff + jitter(backoff)).await;
+                    backoff = (backoff * 2).min(self.config.max_backoff);
+                }
+                Err(e) => {
+                    error!(
+                        "reconnect dfdaemon client failed after {} attempt(s): {}",
+                        attempt, e
+                    );
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    // health_check probes whether the dfdaemon is reachable without disturbing the client
+    // currently in use.
+    pub async fn health_check(&self) -> bool {
+        self.endpoint.connect().await.is_ok()
     }
 
     // get_piece_numbers gets the piece numbers.
@@ -422,26 +1080,55 @@ impl DfdaemonClient {
         &self,
         request: GetPieceNumbersRequest,
     ) -> ClientResult<Vec<i32>> {
-        let mut request = tonic::Request::new(request);
-        request.set_timeout(super::REQUEST_TIMEOUT);
+        let mut tonic_request = tonic::Request::new(request.clone());
+        tonic_request.set_timeout(super::REQUEST_TIMEOUT);
 
-        let response = self.client.clone().get_piece_numbers(request).await?;
-        Ok(response.into_inner().piece_numbers)
+        match self.client().get_piece_numbers(tonic_request).await {
+            Ok(response) => Ok(response.into_inner().piece_numbers),
+            Err(status) if is_reconnectable(&status) => {
+                error!("get piece numbers failed, reconnecting: {}", status);
+
+                let mut tonic_request = tonic::Request::new(request);
+                tonic_request.set_timeout(super::REQUEST_TIMEOUT);
+
+                let response = self
+                    .reconnect()
+                    .await?
+                    .get_piece_numbers(tonic_request)
+                    .await?;
+                Ok(response.into_inner().piece_numbers)
+            }
+            Err(status) => Err(status.into()),
+        }
     }
 
-    // sync_pieces syncs the pieces.
+    // sync_pieces syncs the pieces. The in_stream_rx keeps the RPC open so the caller can keep
+    // sending interested pieces requests as it learns which parents hold which pieces, instead
+    // of opening a new RPC for every round of interest. Because the request body is a stream
+    // that can only be consumed once, a broken connection cannot be replayed transparently here;
+    // the client is reconnected so the *next* call succeeds, and this call's error is returned
+    // to the caller.
     pub async fn sync_pieces(
         &self,
-        request: SyncPiecesRequest,
+        in_stream_rx: mpsc::Receiver<SyncPiecesRequest>,
     ) -> ClientResult<tonic::Response<tonic::codec::Streaming<SyncPiecesResponse>>> {
-        let mut request = tonic::Request::new(request);
+        let mut request = tonic::Request::new(ReceiverStream::new(in_stream_rx));
         request.set_timeout(super::REQUEST_TIMEOUT);
 
-        let response = self.client.clone().sync_pieces(request).await?;
-        Ok(response)
+        match self.client().sync_pieces(request).await {
+            Ok(response) => Ok(response),
+            Err(status) if is_reconnectable(&status) => {
+                error!("sync pieces failed, reconnecting: {}", status);
+                self.reconnect().await?;
+                Err(status.into())
+            }
+            Err(status) => Err(status.into()),
+        }
     }
 
-    // download_task tells the dfdaemon to download the task.
+    // download_task tells the dfdaemon to download the task. As with sync_pieces, a broken
+    // connection is healed for subsequent calls but this call is not retried: its error is
+    // returned to the caller.
     pub async fn download_task(
         &self,
         request: DownloadTaskRequest,
@@ -456,44 +1143,110 @@ impl DfdaemonClient {
             .timeout;
 
         // Initialize the request.
-        let mut request = tonic::Request::new(request);
+        let mut tonic_request = tonic::Request::new(request);
 
         // Set the timeout to the request.
         if let Some(timeout) = timeout {
-            request.set_timeout(
+            tonic_request.set_timeout(
                 Duration::try_from(timeout)
                     .map_err(|_| tonic::Status::invalid_argument("invalid timeout"))?,
             );
         }
 
-        let response = self.client.clone().download_task(request).await?;
-        Ok(response)
+        match self.client().download_task(tonic_request).await {
+            Ok(response) => Ok(response),
+            Err(status) if is_reconnectable(&status) => {
+                error!("download task failed, reconnecting: {}", status);
+                self.reconnect().await?;
+                Err(status.into())
+            }
+            Err(status) => Err(status.into()),
+        }
+    }
+
+    // download_task_range behaves like download_task, but only materializes the requested
+    // byte range on the caller's side: it collects the content chunks the dfdaemon streams
+    // back for `request.download.range` instead of requiring the caller to read the whole
+    // assembled file, enabling partial content delivery (e.g. an HTTP Range response).
+    pub async fn download_task_range(&self, request: DownloadTaskRequest) -> ClientResult<Vec<u8>> {
+        let response = self.download_task(request).await?;
+        let mut in_stream = response.into_inner();
+
+        let mut content = Vec::new();
+        while let Some(response) = in_stream.next().await {
+            let response = response?;
+            if let Some(piece) = response.piece {
+                if let Some(chunk) = piece.content {
+                    content.extend_from_slice(&chunk);
+                }
+            }
+        }
+
+        Ok(content)
     }
 
-    // upload_task tells the dfdaemon to upload the task.
-    pub async fn upload_task(&self, request: UploadTaskRequest) -> ClientResult<()> {
-        let mut request = tonic::Request::new(request);
+    // upload_task tells the dfdaemon to seed a task pushed over in_stream_rx: the first item
+    // must be the upload metadata, every item after that is an ordered content chunk. Returns
+    // once the dfdaemon has persisted every piece and registered the task with the scheduler.
+    // The channel is bounded so a large upload does not need to be buffered fully in memory on
+    // the caller's side either. As with sync_pieces, a broken connection cannot be replayed
+    // here because the chunk stream can only be consumed once; the client is reconnected for
+    // the *next* call and this call's error is returned to the caller.
+    pub async fn upload_task(
+        &self,
+        in_stream_rx: mpsc::Receiver<UploadTaskRequest>,
+    ) -> ClientResult<()> {
+        let mut request = tonic::Request::new(ReceiverStream::new(in_stream_rx));
         request.set_timeout(super::REQUEST_TIMEOUT);
 
-        self.client.clone().upload_task(request).await?;
-        Ok(())
+        match self.client().upload_task(request).await {
+            Ok(_) => Ok(()),
+            Err(status) if is_reconnectable(&status) => {
+                error!("upload task failed, reconnecting: {}", status);
+                self.reconnect().await?;
+                Err(status.into())
+            }
+            Err(status) => Err(status.into()),
+        }
     }
 
     // stat_task gets the status of the task.
     pub async fn stat_task(&self, request: DfdaemonStatTaskRequest) -> ClientResult<Task> {
-        let mut request = tonic::Request::new(request);
-        request.set_timeout(super::REQUEST_TIMEOUT);
+        let mut tonic_request = tonic::Request::new(request.clone());
+        tonic_request.set_timeout(super::REQUEST_TIMEOUT);
 
-        let response = self.client.clone().stat_task(request).await?;
-        Ok(response.into_inner())
+        match self.client().stat_task(tonic_request).await {
+            Ok(response) => Ok(response.into_inner()),
+            Err(status) if is_reconnectable(&status) => {
+                error!("stat task failed, reconnecting: {}", status);
+
+                let mut tonic_request = tonic::Request::new(request);
+                tonic_request.set_timeout(super::REQUEST_TIMEOUT);
+
+                let response = self.reconnect().await?.stat_task(tonic_request).await?;
+                Ok(response.into_inner())
+            }
+            Err(status) => Err(status.into()),
+        }
     }
 
     // delete_task tells the dfdaemon to delete the task.
     pub async fn delete_task(&self, request: DeleteTaskRequest) -> ClientResult<()> {
-        let mut request = tonic::Request::new(request);
-        request.set_timeout(super::REQUEST_TIMEOUT);
+        let mut tonic_request = tonic::Request::new(request.clone());
+        tonic_request.set_timeout(super::REQUEST_TIMEOUT);
+
+        match self.client().delete_task(tonic_request).await {
+            Ok(_) => Ok(()),
+            Err(status) if is_reconnectable(&status) => {
+                error!("delete task failed, reconnecting: {}", status);
 
-        self.client.clone().delete_task(request).await?;
-        Ok(())
+                let mut tonic_request = tonic::Request::new(request);
+                tonic_request.set_timeout(super::REQUEST_TIMEOUT);
+
+                self.reconnect().await?.delete_task(tonic_request).await?;
+                Ok(())
+            }
+            Err(status) => Err(status.into()),
+        }
     }
 }
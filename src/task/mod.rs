@@ -0,0 +1,144 @@
+/*
+ *     Copyright 2023 The Dragonfly Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// NOTE: `task::Task`'s full definition (piece storage, scheduler client, download orchestration)
+// lives outside this snapshot of the repository, so it is not redeclared here. This file only
+// adds what the rest of the crate needs from the `task` module: the `scrub` submodule and the
+// digest helpers shared by `scrub` and `grpc::dfdaemon`, so that neither has to assume a single
+// hard-coded hash algorithm.
+
+pub mod scrub;
+
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+// DEFAULT_SCRUB_SCAN_INTERVAL is how often `scrub::Scrubber` should complete a full pass over
+// every locally stored piece.
+pub(crate) const DEFAULT_SCRUB_SCAN_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+// PieceHasher incrementally hashes piece content with whichever algorithm a digest names, so
+// verification never has to assume a single hash algorithm is in use.
+enum PieceHasher {
+    Sha256(sha2::Sha256),
+    Sha512(sha2::Sha512),
+}
+
+impl PieceHasher {
+    // for_algorithm returns a hasher for the named algorithm, or None if it is not recognized.
+    fn for_algorithm(algorithm: &str) -> Option<Self> {
+        use sha2::Digest as _;
+        match algorithm {
+            "sha256" => Some(Self::Sha256(sha2::Sha256::new())),
+            "sha512" => Some(Self::Sha512(sha2::Sha512::new())),
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        use sha2::Digest as _;
+        match self {
+            Self::Sha256(hasher) => hasher.update(data),
+            Self::Sha512(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finalize_hex(self) -> String {
+        use sha2::Digest as _;
+        match self {
+            Self::Sha256(hasher) => format!("{:x}", hasher.finalize()),
+            Self::Sha512(hasher) => format!("{:x}", hasher.finalize()),
+        }
+    }
+}
+
+// digest_algorithm returns the algorithm name a "<algorithm>:<hex>" digest was computed with, or
+// None if the digest is not in that form.
+pub(crate) fn digest_algorithm(digest: &str) -> Option<&str> {
+    digest.split_once(':').map(|(algorithm, _)| algorithm)
+}
+
+// compute_digest hashes in-memory content with the named algorithm, returning the digest in
+// "<algorithm>:<hex>" form. Returns None if the algorithm is not recognized.
+pub(crate) fn compute_digest(content: &[u8], algorithm: &str) -> Option<String> {
+    let mut hasher = PieceHasher::for_algorithm(algorithm)?;
+    hasher.update(content);
+    Some(format!("{}:{}", algorithm, hasher.finalize_hex()))
+}
+
+// compute_piece_digest hashes a piece reader's content with the named algorithm, reading in
+// fixed-size frames so the check stays memory-bounded regardless of piece size. Returns None if
+// the algorithm is not recognized, rather than guessing at one.
+pub(crate) async fn compute_piece_digest(
+    reader: &mut (impl AsyncRead + Unpin),
+    algorithm: &str,
+    frame_size: usize,
+) -> std::io::Result<Option<String>> {
+    let Some(mut hasher) = PieceHasher::for_algorithm(algorithm) else {
+        return Ok(None);
+    };
+
+    let mut frame = vec![0u8; frame_size];
+    loop {
+        let n = reader.read(&mut frame).await?;
+        if n == 0 {
+            break;
+        }
+
+        hasher.update(&frame[..n]);
+    }
+
+    Ok(Some(format!("{}:{}", algorithm, hasher.finalize_hex())))
+}
+
+// PieceDigestStreamVerifier incrementally hashes a piece's content as a caller forwards it
+// frame-by-frame, instead of requiring the whole piece to be buffered before it can be
+// verified. An unrecognized digest format or algorithm can't be checked by `finish`, so it is
+// treated as verified, the same "trust storage, don't refuse valid content" policy as
+// `compute_piece_digest`.
+pub(crate) struct PieceDigestStreamVerifier {
+    hasher: Option<PieceHasher>,
+    algorithm: String,
+}
+
+impl PieceDigestStreamVerifier {
+    // new starts a verifier for the given expected digest.
+    pub(crate) fn new(expected_digest: &str) -> Self {
+        let algorithm = digest_algorithm(expected_digest).unwrap_or_default();
+        Self {
+            hasher: PieceHasher::for_algorithm(algorithm),
+            algorithm: algorithm.to_string(),
+        }
+    }
+
+    // update feeds the next frame of the piece's content into the running hash.
+    pub(crate) fn update(&mut self, frame: &[u8]) {
+        if let Some(hasher) = &mut self.hasher {
+            hasher.update(frame);
+        }
+    }
+
+    // finish compares the accumulated hash of every frame fed through update against
+    // expected_digest.
+    pub(crate) fn finish(self, expected_digest: &str) -> bool {
+        match self.hasher {
+            Some(hasher) => {
+                let actual_digest = format!("{}:{}", self.algorithm, hasher.finalize_hex());
+                actual_digest == expected_digest
+            }
+            None => true,
+        }
+    }
+}
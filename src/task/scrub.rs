@@ -0,0 +1,212 @@
+/*
+ *     Copyright 2023 The Dragonfly Authors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// NOTE: `task::Task` and `task::piece::Piece` live outside this snapshot of the repository, so
+// this module cannot construct one itself. It is wired in from `grpc::dfdaemon::DfdaemonServer`,
+// the one place in this snapshot that already owns an `Arc<task::Task>` and spawns background
+// work alongside it (see `DfdaemonServer::run`).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::Mutex;
+use tracing::{error, info};
+
+// SCRUB_FRAME_SIZE is the size of each frame read while recomputing a piece's digest.
+const SCRUB_FRAME_SIZE: usize = 64 * 1024;
+
+// SCRUB_ERROR_RETRY_DELAY is how long `Scrubber::run` waits before retrying a pass that failed
+// outright (e.g. local storage was briefly unavailable), so a persistent failure logs and spins
+// at a sane rate instead of pegging a core.
+const SCRUB_ERROR_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+// Scrubber walks all locally stored pieces of every task at a throttled rate, verifying each
+// piece's digest and marking corrupt pieces for re-download, the way block stores self-heal
+// silent corruption.
+pub struct Scrubber {
+    // task is the task manager that owns local storage and the scheduler client.
+    task: Arc<crate::task::Task>,
+
+    // scan_interval is how often a full pass over all locally stored pieces should complete.
+    scan_interval: Duration,
+
+    // last_verified_at records, per task id and piece number, the last time a piece was
+    // verified, so a full pass is spread over scan_interval instead of hammering disk at once.
+    last_verified_at: Mutex<HashMap<(String, i32), SystemTime>>,
+}
+
+impl Scrubber {
+    // new creates a new Scrubber over the given task manager.
+    pub fn new(task: Arc<crate::task::Task>, scan_interval: Duration) -> Self {
+        Self {
+            task,
+            scan_interval,
+            last_verified_at: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // run walks all locally stored pieces forever, throttling itself so a full pass is spread
+    // over scan_interval rather than reading every piece back-to-back.
+    pub async fn run(&self) {
+        loop {
+            if let Err(e) = self.scrub_all().await {
+                error!("scrub local pieces: {}", e);
+
+                // Back off before retrying so a persistent failure (e.g. local storage
+                // unavailable) logs and retries at a sane rate instead of immediately
+                // re-entering scrub_all in a tight, core-pegging loop.
+                tokio::time::sleep(SCRUB_ERROR_RETRY_DELAY).await;
+            }
+        }
+    }
+
+    // scrub_all verifies every locally stored piece across every task, throttled so the full
+    // pass takes roughly scan_interval regardless of how many pieces are stored.
+    async fn scrub_all(&self) -> crate::Result<()> {
+        let task_ids = self.task.piece.get_all_task_ids()?;
+        if task_ids.is_empty() {
+            tokio::time::sleep(self.scan_interval).await;
+            return Ok(());
+        }
+
+        // Spread the whole pass evenly across every piece we are about to verify, so scrubbing
+        // never hammers disk all at once.
+        let total_pieces: usize = task_ids
+            .iter()
+            .map(|task_id| {
+                self.task
+                    .piece
+                    .get_all(task_id)
+                    .map(|pieces| pieces.len())
+                    .unwrap_or(0)
+            })
+            .sum();
+        let per_piece_delay = self
+            .scan_interval
+            .checked_div(total_pieces.max(1) as u32)
+            .unwrap_or(self.scan_interval);
+
+        // Once a full pass catches up, every piece's last_verified_at is fresh and every
+        // piece in the loop below is skipped without ever sleeping. Track whether this pass
+        // actually did any work so that steady-state case can fall back to sleeping a full
+        // scan_interval instead of immediately looping back into get_all_task_ids/get_all
+        // for every task, back-to-back, for as long as everything stays verified.
+        let mut verified_any = false;
+
+        for task_id in task_ids {
+            let pieces = self.task.piece.get_all(&task_id)?;
+            for piece in pieces {
+                // Skip pieces verified within the last scan_interval instead of re-hashing
+                // them every pass, so last_verified_at actually spreads a full pass across
+                // scan_interval rather than the per_piece_delay sleep doing that alone.
+                if !self.needs_verification(&task_id, piece.number).await {
+                    continue;
+                }
+
+                self.scrub_piece(&task_id, piece.number, &piece.digest)
+                    .await;
+                verified_any = true;
+                tokio::time::sleep(per_piece_delay).await;
+            }
+        }
+
+        if !verified_any {
+            tokio::time::sleep(self.scan_interval).await;
+        }
+
+        Ok(())
+    }
+
+    // needs_verification reports whether a piece has not been verified within the last
+    // scan_interval. This is an in-memory throttle only: a restart forgets it and the next pass
+    // re-verifies everything, which is the safe direction to fail in (it never skips a piece
+    // that genuinely needs checking).
+    async fn needs_verification(&self, task_id: &str, piece_number: i32) -> bool {
+        let last_verified_at = self.last_verified_at.lock().await;
+        match last_verified_at.get(&(task_id.to_string(), piece_number)) {
+            Some(verified_at) => verified_at
+                .elapsed()
+                .map(|elapsed| elapsed >= self.scan_interval)
+                .unwrap_or(true),
+            None => true,
+        }
+    }
+
+    // scrub_piece recomputes a single piece's digest and marks it for re-download from the
+    // scheduler if it no longer matches what is stored in local storage.
+    async fn scrub_piece(&self, task_id: &str, piece_number: i32, expected_digest: &str) {
+        let mut reader = match self
+            .task
+            .piece
+            .download_from_local_peer(task_id, piece_number)
+            .await
+        {
+            Ok(reader) => reader,
+            Err(e) => {
+                error!("get piece content from local peer: {}", e);
+                return;
+            }
+        };
+
+        // An unrecognized digest format or algorithm is not something this scrubber can check,
+        // so it is recorded as verified (there is nothing more to do) rather than spuriously
+        // marking healthy pieces for re-download.
+        let Some(algorithm) = super::digest_algorithm(expected_digest) else {
+            self.record_verified(task_id, piece_number).await;
+            return;
+        };
+
+        let actual_digest =
+            match super::compute_piece_digest(&mut reader, algorithm, SCRUB_FRAME_SIZE).await {
+                Ok(Some(digest)) => digest,
+                Ok(None) => {
+                    self.record_verified(task_id, piece_number).await;
+                    return;
+                }
+                Err(e) => {
+                    error!("read piece content: {}", e);
+                    return;
+                }
+            };
+
+        self.record_verified(task_id, piece_number).await;
+        if actual_digest == expected_digest {
+            return;
+        }
+
+        error!(
+            "piece {} of task {} is corrupt (expected {}, got {}), marking for re-download",
+            piece_number, task_id, expected_digest, actual_digest
+        );
+
+        if let Err(e) = self.task.piece.mark_for_redownload(task_id, piece_number) {
+            error!("mark piece for re-download: {}", e);
+            return;
+        }
+
+        info!(
+            "scrub marked piece {} of task {} for re-download from the scheduler",
+            piece_number, task_id
+        );
+    }
+
+    // record_verified notes that a piece was just checked, so needs_verification can skip it
+    // until the next scan_interval has elapsed.
+    async fn record_verified(&self, task_id: &str, piece_number: i32) {
+        let mut last_verified_at = self.last_verified_at.lock().await;
+        last_verified_at.insert((task_id.to_string(), piece_number), SystemTime::now());
+    }
+}